@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::model::AssertionType;
+
+/// A single assertion rule parsed out of `CaseModel.expected_result`.
+///
+/// `expected_result` is stored as free text so older cases that only ever
+/// held a plain string keep working: if it doesn't parse as one of these
+/// rule sets we fall back to a substring match against the response body.
+#[derive(Debug, Deserialize)]
+struct ExpectedRules {
+    status: Option<u16>,
+    #[serde(default)]
+    json_path: std::collections::BTreeMap<String, Value>,
+    #[serde(default)]
+    body_contains: Vec<String>,
+    #[serde(default)]
+    headers: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssertionFailure {
+    pub rule: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssertionReport {
+    pub passed: bool,
+    pub failures: Vec<AssertionFailure>,
+}
+
+impl AssertionReport {
+    fn ok() -> Self {
+        Self {
+            passed: true,
+            failures: Vec::new(),
+        }
+    }
+
+    fn fail(rule: impl Into<String>, expected: Value, actual: Value) -> AssertionFailure {
+        AssertionFailure {
+            rule: rule.into(),
+            expected,
+            actual,
+        }
+    }
+}
+
+/// Walks a `$.foo.bar[0]` / `foo.bar[0]`-style dotted path over a
+/// `serde_json::Value`. Supports the leading `$`, dotted object keys and
+/// `[index]` array access — enough for the assertion rules a test case
+/// needs, without pulling in a full JSONPath crate.
+fn json_path_get<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let mut current = root;
+
+    for raw_segment in path.split('.') {
+        let segment = raw_segment.trim_start_matches('.');
+        if segment.is_empty() {
+            continue;
+        }
+
+        let (key, indices) = parse_segment(segment);
+        if !key.is_empty() {
+            current = current.get(key)?;
+        }
+        for index in indices {
+            current = current.get(index)?;
+        }
+    }
+
+    Some(current)
+}
+
+/// Splits `foo[0][1]` into (`"foo"`, `[0, 1]`); `[0]` alone yields (`""`, `[0]`).
+fn parse_segment(segment: &str) -> (&str, Vec<usize>) {
+    let mut indices = Vec::new();
+    let key_end = segment.find('[').unwrap_or(segment.len());
+    let (key, mut rest) = segment.split_at(key_end);
+
+    while let Some(start) = rest.find('[') {
+        if let Some(end) = rest[start..].find(']') {
+            if let Ok(idx) = rest[start + 1..start + end].parse::<usize>() {
+                indices.push(idx);
+            }
+            rest = &rest[start + end + 1..];
+        } else {
+            break;
+        }
+    }
+
+    (key, indices)
+}
+
+/// Evaluates `expected_result` against the live response and reports
+/// pass/fail per rule. Falls back to a plain substring check when
+/// `expected_result` isn't valid JSON, so plain-text cases keep working.
+pub fn evaluate(
+    expected_result: &str,
+    status: u16,
+    headers: &reqwest::header::HeaderMap,
+    body: &str,
+) -> AssertionReport {
+    if expected_result.trim().is_empty() {
+        return AssertionReport::ok();
+    }
+
+    let rules: ExpectedRules = match serde_json::from_str(expected_result) {
+        Ok(rules) => rules,
+        Err(_) => {
+            return if body.contains(expected_result) {
+                AssertionReport::ok()
+            } else {
+                AssertionReport {
+                    passed: false,
+                    failures: vec![AssertionReport::fail(
+                        "body_contains",
+                        Value::String(expected_result.to_string()),
+                        Value::String(body.to_string()),
+                    )],
+                }
+            }
+        }
+    };
+
+    let mut failures = Vec::new();
+
+    if let Some(expected_status) = rules.status {
+        if expected_status != status {
+            failures.push(AssertionReport::fail(
+                "status",
+                Value::from(expected_status),
+                Value::from(status),
+            ));
+        }
+    }
+
+    if !rules.json_path.is_empty() {
+        let body_json: Value = serde_json::from_str(body).unwrap_or(Value::Null);
+        for (path, expected) in &rules.json_path {
+            let actual = json_path_get(&body_json, path).cloned().unwrap_or(Value::Null);
+            if &actual != expected {
+                failures.push(AssertionReport::fail(
+                    format!("json_path:{}", path),
+                    expected.clone(),
+                    actual,
+                ));
+            }
+        }
+    }
+
+    for needle in &rules.body_contains {
+        if !body.contains(needle.as_str()) {
+            failures.push(AssertionReport::fail(
+                "body_contains",
+                Value::String(needle.clone()),
+                Value::String(body.to_string()),
+            ));
+        }
+    }
+
+    for (header_name, expected_value) in &rules.headers {
+        let actual_value = headers
+            .get(header_name.as_str())
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+        if actual_value != expected_value {
+            failures.push(AssertionReport::fail(
+                format!("headers:{}", header_name),
+                Value::String(expected_value.clone()),
+                Value::String(actual_value.to_string()),
+            ));
+        }
+    }
+
+    AssertionReport {
+        passed: failures.is_empty(),
+        failures,
+    }
+}
+
+/// Picks the explicit `assertion_type` evaluator when a case has one set,
+/// falling back to the legacy JSON-rule-set/substring `evaluate` for older
+/// cases that predate the `assertion_type` column.
+pub fn evaluate_case(
+    assertion_type: Option<&str>,
+    expected_result: &str,
+    status: u16,
+    headers: &reqwest::header::HeaderMap,
+    body: &str,
+) -> AssertionReport {
+    match assertion_type {
+        Some(raw) => evaluate_with_type(AssertionType::parse(Some(raw)), expected_result, status, body),
+        None => evaluate(expected_result, status, headers, body),
+    }
+}
+
+/// Evaluates `expected_result` according to an explicit `assertion_type`
+/// rather than sniffing it as a JSON rule set. `json_path` expects
+/// `expected_result` in `path=value` form, e.g. `data.user.id=123`.
+pub fn evaluate_with_type(
+    assertion_type: AssertionType,
+    expected_result: &str,
+    status: u16,
+    body: &str,
+) -> AssertionReport {
+    match assertion_type {
+        AssertionType::Exact => {
+            if body == expected_result {
+                AssertionReport::ok()
+            } else {
+                AssertionReport {
+                    passed: false,
+                    failures: vec![AssertionReport::fail(
+                        "exact",
+                        Value::String(expected_result.to_string()),
+                        Value::String(body.to_string()),
+                    )],
+                }
+            }
+        }
+        AssertionType::Contains => {
+            if body.contains(expected_result) {
+                AssertionReport::ok()
+            } else {
+                AssertionReport {
+                    passed: false,
+                    failures: vec![AssertionReport::fail(
+                        "contains",
+                        Value::String(expected_result.to_string()),
+                        Value::String(body.to_string()),
+                    )],
+                }
+            }
+        }
+        AssertionType::StatusCode => match expected_result.trim().parse::<u16>() {
+            Ok(expected_status) if expected_status == status => AssertionReport::ok(),
+            Ok(expected_status) => AssertionReport {
+                passed: false,
+                failures: vec![AssertionReport::fail(
+                    "status_code",
+                    Value::from(expected_status),
+                    Value::from(status),
+                )],
+            },
+            Err(_) => AssertionReport {
+                passed: false,
+                failures: vec![AssertionReport::fail(
+                    "status_code",
+                    Value::String(expected_result.to_string()),
+                    Value::String("expected_result is not a valid status code".to_string()),
+                )],
+            },
+        },
+        AssertionType::JsonPath => {
+            let (path, expected_value) = match expected_result.split_once('=') {
+                Some((path, value)) => (path, value),
+                None => (expected_result, ""),
+            };
+            let body_json: Value = serde_json::from_str(body).unwrap_or(Value::Null);
+            let actual = json_path_get(&body_json, path).cloned().unwrap_or(Value::Null);
+            let actual_str = match &actual {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            if actual_str == expected_value {
+                AssertionReport::ok()
+            } else {
+                AssertionReport {
+                    passed: false,
+                    failures: vec![AssertionReport::fail(
+                        format!("json_path:{}", path),
+                        Value::String(expected_value.to_string()),
+                        actual,
+                    )],
+                }
+            }
+        }
+        AssertionType::Regex => match regex::Regex::new(expected_result) {
+            Ok(re) if re.is_match(body) => AssertionReport::ok(),
+            Ok(_) => AssertionReport {
+                passed: false,
+                failures: vec![AssertionReport::fail(
+                    "regex",
+                    Value::String(expected_result.to_string()),
+                    Value::String(body.to_string()),
+                )],
+            },
+            Err(e) => AssertionReport {
+                passed: false,
+                failures: vec![AssertionReport::fail(
+                    "regex",
+                    Value::String(expected_result.to_string()),
+                    Value::String(format!("invalid regex: {}", e)),
+                )],
+            },
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_segment_splits_key_and_indices() {
+        assert_eq!(parse_segment("foo"), ("foo", vec![]));
+        assert_eq!(parse_segment("foo[0]"), ("foo", vec![0]));
+        assert_eq!(parse_segment("foo[0][1]"), ("foo", vec![0, 1]));
+        assert_eq!(parse_segment("[2]"), ("", vec![2]));
+    }
+
+    #[test]
+    fn json_path_get_walks_dotted_and_bracket_segments() {
+        let root = json!({
+            "data": {
+                "users": [
+                    {"id": 1},
+                    {"id": 2},
+                ],
+            },
+        });
+
+        assert_eq!(
+            json_path_get(&root, "$.data.users[1].id"),
+            Some(&json!(2))
+        );
+        assert_eq!(
+            json_path_get(&root, "data.users[0].id"),
+            Some(&json!(1))
+        );
+    }
+
+    #[test]
+    fn json_path_get_returns_none_for_missing_path() {
+        let root = json!({"data": {}});
+        assert_eq!(json_path_get(&root, "data.missing"), None);
+        assert_eq!(json_path_get(&root, "data.users[0]"), None);
+    }
+}
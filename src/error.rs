@@ -0,0 +1,82 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+/// Single error type for handlers to return via `?`, replacing the
+/// hand-rolled `(StatusCode, Json<Value>)` tuple every handler used to
+/// build by hand.
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("database error: {0}")]
+    Sqlx(sqlx::Error),
+    #[error("user with that email already exists")]
+    UserExists,
+    #[error("case with that title already exists")]
+    CaseExists,
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("invalid email or password")]
+    InvalidCredentials,
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("password hashing error: {0}")]
+    Hash(#[from] argon2::password_hash::Error),
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("jwt error: {0}")]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, message) = match &self {
+            AppError::Sqlx(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e)),
+            AppError::UserExists => (StatusCode::CONFLICT, self.to_string()),
+            AppError::CaseExists => (StatusCode::CONFLICT, self.to_string()),
+            AppError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
+            AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AppError::InvalidCredentials => (StatusCode::BAD_REQUEST, self.to_string()),
+            AppError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
+            AppError::Hash(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            AppError::Http(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            AppError::Jwt(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            AppError::Forbidden(_) => (StatusCode::FORBIDDEN, self.to_string()),
+        };
+
+        let status_label = if status.is_client_error() || status.is_server_error() {
+            "fail"
+        } else {
+            "success"
+        };
+
+        (status, Json(json!({"status": status_label, "message": message}))).into_response()
+    }
+}
+
+/// sqlx surfaces unique-constraint violations as a generic `Database`
+/// error, so the exact table has to be sniffed out of the driver message.
+/// This supersedes the `"Duplicate entry"` string-matching and the
+/// pre-`SELECT EXISTS` check that used to live next to each INSERT.
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        if let sqlx::Error::Database(ref db_err) = err {
+            if db_err.is_unique_violation() {
+                let message = db_err.message();
+                if message.contains("users") {
+                    return AppError::UserExists;
+                }
+                if message.contains("cases") {
+                    return AppError::CaseExists;
+                }
+            }
+        }
+        AppError::Sqlx(err)
+    }
+}
@@ -1,26 +1,40 @@
+use std::str::FromStr;
 use std::sync::Arc;
 
 use argon2::{password_hash::SaltString, Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use axum::{
     extract::{Path, Query, State},
     http::{header, Response, StatusCode},
-    response::IntoResponse,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
     Extension, Json,
 };
+use futures_util::stream::Stream;
+use tokio_stream::wrappers::UnboundedReceiverStream;
 use axum_extra::extract::cookie::{Cookie, SameSite};
 use jsonwebtoken::{encode, EncodingKey, Header};
 use rand_core::OsRng;
 use serde_json::json;
 
 use crate::{
-    model::{LoginUserSchema, RegisterUserSchema, TokenClaims, User},
+    error::AppError,
+    model::{LoginUserSchema, RefreshToken, RefreshTokenSchema, RegisterUserSchema, TokenClaims, User},
+    refresh,
     response::FilteredUser,
     AppState,
 };
 
 use crate::{
-    model::{CaseModel, CaseModelResponse, CaseModelAllResponse},
-    schema::{CreateCaseSchema, FilterOptions, UpdateCaseSchema},
+    assertions,
+    http_client,
+    model::{
+        CaseHistory, CaseModel, CaseModelResponse, CaseModelAllResponse, CaseRun, CaseRunResult,
+        CaseRunSummary, LastStatus,
+    },
+    ratelimit::LimitType,
+    schema::{CreateCaseSchema, FilterOptions, HistoryOptions, RunCasesSchema, UpdateCaseSchema},
 };
 
 pub async fn health_checker_handler() -> impl IntoResponse {
@@ -34,46 +48,25 @@ pub async fn health_checker_handler() -> impl IntoResponse {
     Json(json_response)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/register",
+    request_body = RegisterUserSchema,
+    responses(
+        (status = 200, description = "User created"),
+        (status = 409, description = "User already exists"),
+    )
+)]
 pub async fn register_user_handler(
     State(data): State<Arc<AppState>>,
     Json(body): Json<RegisterUserSchema>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let user_exists: Option<bool> =
-        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE email = ?)")
-            .bind(body.email.to_owned().to_ascii_lowercase())
-            .fetch_one(&data.db)
-            .await
-            .map_err(|e| {
-                let error_response = serde_json::json!({
-                    "status": "fail",
-                    "message": format!("Database error: {}", e),
-                });
-                (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
-            })?;
-
-    if let Some(exists) = user_exists {
-        if exists {
-            let error_response = serde_json::json!({
-                "status": "fail",
-                "message": "User with that email already exists",
-            });
-            return Err((StatusCode::CONFLICT, Json(error_response)));
-        }
-    }
-
+) -> Result<impl IntoResponse, AppError> {
     let salt = SaltString::generate(&mut OsRng);
     let hashed_password = Argon2::default()
-        .hash_password(body.password.as_bytes(), &salt)
-        .map_err(|e| {
-            let error_response = serde_json::json!({
-                "status": "fail",
-                "message": format!("Error while hashing password: {}", e),
-            });
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
-        })
-        .map(|hash| hash.to_string())?;
+        .hash_password(body.password.as_bytes(), &salt)?
+        .to_string();
 
-    let user= sqlx::query_as!(
+    sqlx::query_as!(
         User,
         r#"INSERT INTO users (name,email,password) VALUES (?, ?, ?)"#,
         body.name.to_string(),
@@ -81,74 +74,43 @@ pub async fn register_user_handler(
         hashed_password
     )
     .fetch_optional(&data.db)
-    .await
-    .map_err(|e| {
-        let error_response = serde_json::json!({
-            "status": "fail",
-            "message": format!("Database error: {}", e),
-        });
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
-    })?;
+    .await?;
 
-    let user = sqlx::query_as!(User, 
-        "SELECT * FROM users WHERE email = ?", 
+    let user = sqlx::query_as!(User,
+        "SELECT * FROM users WHERE email = ?",
         body.email.to_ascii_lowercase()
     )
     .fetch_optional(&data.db)
-    .await
-    .map_err(|e| {
-        let error_response = serde_json::json!({
-            "status": "error",
-            "message": format!("Database error: {}", e),
-        });
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
-    })?;
+    .await?
+    .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
-    // let user_response = serde_json::json!({"status": "success","data": serde_json::json!({
-    //     "user": filter_user_record(&user)
-    // })});
-    // Ok(Json(user_response))
-
-    if let Some(user) = user {
-        let user_response = serde_json::json!({"status": "success","data": serde_json::json!({
-            "user": filter_user_record(&user)
-        })});
-    
-        Ok(Json(user_response))
-    } else {
-        let error_response = serde_json::json!({
-            "status": "fail",
-            "message": "User not found",
-        });
-        Err((StatusCode::NOT_FOUND, Json(error_response)))
-    }
+    let user_response = serde_json::json!({"status": "success","data": serde_json::json!({
+        "user": filter_user_record(&user)
+    })});
 
+    Ok(Json(user_response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    request_body = LoginUserSchema,
+    responses(
+        (status = 200, description = "Authenticated"),
+        (status = 400, description = "Invalid credentials"),
+    )
+)]
 pub async fn login_user_handler(
     State(data): State<Arc<AppState>>,
     Json(body): Json<LoginUserSchema>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let user = sqlx::query_as!(User, 
-        "SELECT * FROM users WHERE email = ?", 
+) -> Result<impl IntoResponse, AppError> {
+    let user = sqlx::query_as!(User,
+        "SELECT * FROM users WHERE email = ?",
         body.email.to_ascii_lowercase()
     )
     .fetch_optional(&data.db)
-    .await
-    .map_err(|e| {
-        let error_response = serde_json::json!({
-            "status": "error",
-            "message": format!("Database error: {}", e),
-        });
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
-    })?
-    .ok_or_else(|| {
-        let error_response = serde_json::json!({
-            "status": "fail",
-            "message": "Invalid email or password",
-        });
-        (StatusCode::BAD_REQUEST, Json(error_response))
-    })?;
+    .await?
+    .ok_or(AppError::InvalidCredentials)?;
 
     let is_valid = match PasswordHash::new(&user.password) {
         Ok(parsed_hash) => Argon2::default()
@@ -158,20 +120,18 @@ pub async fn login_user_handler(
     };
 
     if !is_valid {
-        let error_response = serde_json::json!({
-            "status": "fail",
-            "message": "Invalid email or password"
-        });
-        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        return Err(AppError::InvalidCredentials);
     }
 
     let now = chrono::Utc::now();
     let iat = now.timestamp() as usize;
     let exp = (now + chrono::Duration::minutes(60)).timestamp() as usize;
+    let jti = uuid::Uuid::new_v4().to_string();
     let claims: TokenClaims = TokenClaims {
         sub: user.id.to_string(),
         exp,
         iat,
+        jti: jti.clone(),
     };
 
     let token = encode(
@@ -181,6 +141,8 @@ pub async fn login_user_handler(
     )
     .unwrap();
 
+    let refresh_token = issue_refresh_token(&data, &user.id, &jti).await?;
+
     let cookie = Cookie::build("token", token.to_owned())
         .path("/")
         .max_age(time::Duration::hours(1))
@@ -188,14 +150,53 @@ pub async fn login_user_handler(
         .http_only(true)
         .finish();
 
-    let mut response = Response::new(json!({"status": "success", "token": token}).to_string());
+    let mut response = Response::new(
+        json!({"status": "success", "token": token, "refresh_token": refresh_token}).to_string(),
+    );
     response
         .headers_mut()
         .insert(header::SET_COOKIE, cookie.to_string().parse().unwrap());
     Ok(response)
 }
 
-pub async fn logout_handler() -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+/// Mints a refresh token for `user_id`/`jti`, persisting only its hash.
+async fn issue_refresh_token(
+    data: &Arc<AppState>,
+    user_id: &str,
+    jti: &str,
+) -> Result<String, sqlx::Error> {
+    let refresh_token = refresh::generate_token();
+    let token_hash = refresh::hash_token(&refresh_token);
+    let expires_at = chrono::Utc::now() + chrono::Duration::days(refresh::REFRESH_TOKEN_TTL_DAYS);
+    let id = uuid::Uuid::new_v4().to_string();
+
+    sqlx::query!(
+        r#"INSERT INTO refresh_tokens (id, user_id, jti, token_hash, expires_at, revoked) VALUES (?, ?, ?, ?, ?, 0)"#,
+        id,
+        user_id,
+        jti,
+        token_hash,
+        expires_at,
+    )
+    .execute(&data.db)
+    .await?;
+
+    Ok(refresh_token)
+}
+
+pub async fn logout_handler(
+    Extension(user): Extension<User>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    // Revoke every outstanding refresh token for this user so the session
+    // truly ends rather than just expiring the access cookie.
+    sqlx::query!(
+        r#"UPDATE refresh_tokens SET revoked = 1 WHERE user_id = ? AND revoked = 0"#,
+        user.id
+    )
+    .execute(&data.db)
+    .await?;
+
     let cookie = Cookie::build("token", "")
         .path("/")
         .max_age(time::Duration::hours(-1))
@@ -210,9 +211,63 @@ pub async fn logout_handler() -> Result<impl IntoResponse, (StatusCode, Json<ser
     Ok(response)
 }
 
+/// `POST /api/auth/refresh` — validates a presented refresh token and
+/// mints a fresh access token, rotating the refresh token so a stolen one
+/// can't be replayed after use.
+pub async fn refresh_token_handler(
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<RefreshTokenSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    let token_hash = refresh::hash_token(&body.refresh_token);
+
+    let stored = sqlx::query_as!(
+        RefreshToken,
+        r#"SELECT * FROM refresh_tokens WHERE token_hash = ?"#,
+        token_hash
+    )
+    .fetch_optional(&data.db)
+    .await?
+    .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".to_string()))?;
+
+    if stored.revoked != 0 || stored.expires_at < chrono::Utc::now() {
+        return Err(AppError::Unauthorized(
+            "Refresh token expired or revoked".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        r#"UPDATE refresh_tokens SET revoked = 1 WHERE id = ?"#,
+        stored.id
+    )
+    .execute(&data.db)
+    .await?;
+
+    let now = chrono::Utc::now();
+    let jti = uuid::Uuid::new_v4().to_string();
+    let claims = TokenClaims {
+        sub: stored.user_id.clone(),
+        iat: now.timestamp() as usize,
+        exp: (now + chrono::Duration::minutes(60)).timestamp() as usize,
+        jti: jti.clone(),
+    };
+
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(data.env.jwt_secret.as_ref()),
+    )
+    .unwrap();
+
+    let new_refresh_token = issue_refresh_token(&data, &stored.user_id, &jti).await?;
+
+    Ok(Json(
+        json!({"status": "success", "token": token, "refresh_token": new_refresh_token}),
+    ))
+}
+
 pub async fn get_me_handler(
     Extension(user): Extension<User>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+) -> Result<impl IntoResponse, AppError> {
     let json_response = serde_json::json!({
         "status":  "success",
         "data": serde_json::json!({
@@ -242,36 +297,49 @@ fn filter_user_record(user: &User) -> FilteredUser {
 // ------------------------- Case-----------------------------//
 // -----------------------------------------------------------//
 
+#[utoipa::path(
+    get,
+    path = "/api/cases",
+    security(("bearerAuth" = [])),
+    params(
+        ("page" = Option<usize>, Query, description = "Page number"),
+        ("limit" = Option<usize>, Query, description = "Page size"),
+    ),
+    responses(
+        (status = 200, description = "Cases", body = [CaseModelResponse]),
+    )
+)]
 pub async fn case_list_handler(
     Extension(user): Extension<User>,
     opts: Option<Query<FilterOptions>>,
     State(data): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+) -> Result<impl IntoResponse, AppError> {
     let Query(opts) = opts.unwrap_or_default();
 
     let limit = opts.limit.unwrap_or(10);
     let offset = (opts.page.unwrap_or(1) - 1) * limit;
 
-    let cases = sqlx::query_as!(
-        CaseModel,
-        // r#"SELECT * FROM cases ORDER by id LIMIT ? OFFSET ?"#,
-        // limit as i32,
-        // offset as i32
-        r#"SELECT * FROM cases WHERE user_id = ? ORDER by id LIMIT ? OFFSET ?"#,
-        user.id,
-        limit as i32,
-        offset as i32
-        
-    )
-    .fetch_all(&data.db)
-    .await
-    .map_err(|e| {
-        let error_response = serde_json::json!({
-            "status": "fail",
-            "message": format!("Database error: {}", e),
-        });
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
-    })?;
+    // Admins see every case; everyone else is scoped to their own.
+    let cases = if is_admin(&user) {
+        sqlx::query_as!(
+            CaseModel,
+            r#"SELECT * FROM cases ORDER by id LIMIT ? OFFSET ?"#,
+            limit as i32,
+            offset as i32
+        )
+        .fetch_all(&data.db)
+        .await
+    } else {
+        sqlx::query_as!(
+            CaseModel,
+            r#"SELECT * FROM cases WHERE user_id = ? ORDER by id LIMIT ? OFFSET ?"#,
+            user.id,
+            limit as i32,
+            offset as i32
+        )
+        .fetch_all(&data.db)
+        .await
+    }?;
 
     let case_responses = cases
         .iter()
@@ -287,53 +355,43 @@ pub async fn case_list_handler(
     Ok(Json(json_response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/cases/",
+    security(("bearerAuth" = [])),
+    request_body = CreateCaseSchema,
+    responses(
+        (status = 200, description = "Case created", body = CaseModelResponse),
+        (status = 409, description = "Case already exists"),
+    )
+)]
 pub async fn create_case_handler(
     Extension(user): Extension<User>,
     State(data): State<Arc<AppState>>,
     Json(body): Json<CreateCaseSchema>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+) -> Result<impl IntoResponse, AppError> {
     println!("[create_case_handler]Get User From Header: {:?}", user);
     let case_id = uuid::Uuid::new_v4().to_string();
-    let query_result =
-        sqlx::query(r#"INSERT INTO cases (id,user_id,title,host,uri,method,request_body,expected_result,category) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"#)
-            .bind(case_id.clone())
-            .bind(user.id.to_string()) // 使用User中的id   
-            .bind(body.title.to_string())
-            .bind(body.host.to_string())
-            .bind(body.uri.to_string())
-            .bind(body.method.to_owned().to_string())
-            .bind(body.request_body.to_string())
-            .bind(body.expected_result.to_string())
-            .bind(body.category.to_owned().unwrap_or_default())
-            .execute(&data.db)
-            .await
-            .map_err(|err: sqlx::Error| err.to_string());
-
-    if let Err(err) = query_result {
-        if err.contains("Duplicate entry") {
-            let error_response = serde_json::json!({
-                "status": "fail",
-                "message": "Case with that title already exists",
-            });
-            return Err((StatusCode::CONFLICT, Json(error_response)));
-        }
-
-        return Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"status": "error","message": format!("{:?}", err)})),
-        ));
-    }
+    sqlx::query(r#"INSERT INTO cases (id,user_id,title,host,uri,method,request_body,expected_result,assertion_type,category,headers,timeout_ms,schedule_seconds) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#)
+        .bind(case_id.clone())
+        .bind(user.id.to_string()) // 使用User中的id
+        .bind(body.title.to_string())
+        .bind(body.host.to_string())
+        .bind(body.uri.to_string())
+        .bind(body.method.to_owned().to_string())
+        .bind(body.request_body.to_string())
+        .bind(body.expected_result.to_string())
+        .bind(body.assertion_type.to_owned())
+        .bind(body.category.to_owned().unwrap_or_default())
+        .bind(body.headers.to_owned())
+        .bind(body.timeout_ms)
+        .bind(body.schedule_seconds)
+        .execute(&data.db)
+        .await?;
 
-    // let case = sqlx::query_as!(CaseModel, r#"SELECT * FROM cases WHERE id = ?"#, user_id)
     let case = sqlx::query_as!(CaseModel, r#"SELECT * FROM cases WHERE id = ?"#, case_id)
         .fetch_one(&data.db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"status": "error","message": format!("{:?}", e)})),
-            )
-        })?;
+        .await?;
 
     let case_response = serde_json::json!({"status": "success","data": serde_json::json!({
         "case": filter_db_record(&case)
@@ -342,10 +400,27 @@ pub async fn create_case_handler(
     Ok(Json(case_response))
 }
 
+/// `admin` may act on any case; a normal `user` is limited to cases they own.
+fn is_admin(user: &User) -> bool {
+    user.role == "admin"
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/cases/{id}",
+    security(("bearerAuth" = [])),
+    params(("id" = String, Path, description = "Case id")),
+    responses(
+        (status = 200, description = "Case", body = CaseModelResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+    )
+)]
 pub async fn get_case_handler(
+    Extension(user): Extension<User>,
     Path(id): Path<uuid::Uuid>,
     State(data): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+) -> Result<impl IntoResponse, AppError> {
     let query_result = sqlx::query_as!(
         CaseModel,
         r#"SELECT * FROM cases WHERE id = ?"#,
@@ -356,33 +431,93 @@ pub async fn get_case_handler(
 
     match query_result {
         Ok(case) => {
+            if !is_admin(&user) && case.user_id != user.id {
+                return Err(AppError::Forbidden(
+                    "You do not have access to this case".to_string(),
+                ));
+            }
+
             let case_response = serde_json::json!({"status": "success","data": serde_json::json!({
                 "case": filter_db_record(&case)
             })});
 
-            return Ok(Json(case_response));
-        }
-        Err(sqlx::Error::RowNotFound) => {
-            let error_response = serde_json::json!({
-                "status": "fail",
-                "message": format!("Case with ID: {} not found", id)
-            });
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
-        }
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"status": "error","message": format!("{:?}", e)})),
-            ));
+            Ok(Json(case_response))
         }
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound(format!(
+            "Case with ID: {} not found",
+            id
+        ))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// `GET /api/cases/:id/history` — the last N recorded runs for a case (both
+/// scheduled and on-demand), plus a pass/fail trend summary.
+pub async fn case_history_handler(
+    Extension(user): Extension<User>,
+    Path(id): Path<uuid::Uuid>,
+    opts: Option<Query<HistoryOptions>>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let case = sqlx::query_as!(CaseModel, r#"SELECT * FROM cases WHERE id = ?"#, id.to_string())
+        .fetch_one(&data.db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => AppError::NotFound(format!("Case with ID: {} not found", id)),
+            e => e.into(),
+        })?;
+
+    if !is_admin(&user) && case.user_id != user.id {
+        return Err(AppError::Forbidden(
+            "You do not have access to this case".to_string(),
+        ));
+    }
+
+    let Query(opts) = opts.unwrap_or_default();
+    let limit = opts.limit.unwrap_or(20) as i64;
+
+    let runs = sqlx::query_as!(
+        CaseRun,
+        r#"SELECT * FROM case_runs WHERE case_id = ? ORDER BY ran_at DESC LIMIT ?"#,
+        case.id,
+        limit
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let passed = runs.iter().filter(|r| r.status == "passed").count();
+    let total = runs.len();
+    let history = CaseHistory {
+        case_id: case.id,
+        total,
+        passed,
+        failed: total - passed,
+        runs,
     };
+
+    let json_response = serde_json::json!({"status": "success", "data": history});
+
+    Ok(Json(json_response))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/api/cases/{id}",
+    security(("bearerAuth" = [])),
+    params(("id" = String, Path, description = "Case id")),
+    request_body = UpdateCaseSchema,
+    responses(
+        (status = 200, description = "Updated", body = CaseModelResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+    )
+)]
 pub async fn edit_case_handler(
+    Extension(user): Extension<User>,
     Path(id): Path<uuid::Uuid>,
     State(data): State<Arc<AppState>>,
     Json(body): Json<UpdateCaseSchema>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+) -> Result<impl IntoResponse, AppError> {
     let query_result = sqlx::query_as!(
         CaseModel,
         r#"SELECT * FROM cases WHERE id = ?"#,
@@ -394,27 +529,32 @@ pub async fn edit_case_handler(
     let case = match query_result {
         Ok(case) => case,
         Err(sqlx::Error::RowNotFound) => {
-            let error_response = serde_json::json!({
-                "status": "fail",
-                "message": format!("Case with ID: {} not found", id)
-            });
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
-        }
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"status": "error","message": format!("{:?}", e)})),
-            ));
+            return Err(AppError::NotFound(format!("Case with ID: {} not found", id)));
         }
+        Err(e) => return Err(e.into()),
     };
 
+    if !is_admin(&user) && case.user_id != user.id {
+        return Err(AppError::Forbidden(
+            "You do not have access to this case".to_string(),
+        ));
+    }
+
     let used = body.used.unwrap_or(case.used != 0);
     let i8_used = used as i8;
 
+    // Only an admin may reassign ownership; a non-admin's `user_id` is
+    // ignored rather than letting them hand their own case to anyone else.
+    let new_user_id = if is_admin(&user) {
+        body.user_id.to_owned().unwrap_or_else(|| case.user_id.clone())
+    } else {
+        case.user_id.clone()
+    };
+
     let update_result = sqlx::query(
-        r#"UPDATE cases SET user_id = ?,title = ?, host = ?, uri = ?, method = ? , request_body = ?, expected_result = ?, category = ?, response_code = ?, response_body = ?, used = ? WHERE id = ?"#,
+        r#"UPDATE cases SET user_id = ?,title = ?, host = ?, uri = ?, method = ? , request_body = ?, expected_result = ?, assertion_type = ?, category = ?, headers = ?, timeout_ms = ?, schedule_seconds = ?, response_code = ?, response_body = ?, used = ? WHERE id = ?"#,
     )
-    .bind(body.user_id.to_owned().unwrap_or_else(|| case.user_id.clone())) // 使用body中的user_id
+    .bind(new_user_id)
 
     .bind(body.title.to_owned().unwrap_or_else(|| case.title.clone()))
     .bind(
@@ -442,11 +582,15 @@ pub async fn edit_case_handler(
             .to_owned()
             .unwrap_or_else(|| case.expected_result.clone().unwrap()),
     )
+    .bind(body.assertion_type.to_owned().or_else(|| case.assertion_type.clone()))
     .bind(
         body.category
             .to_owned()
             .unwrap_or_else(|| case.category.clone().unwrap()),
     )
+    .bind(body.headers.to_owned().or_else(|| case.headers.clone()))
+    .bind(body.timeout_ms.or(case.timeout_ms))
+    .bind(body.schedule_seconds.or(case.schedule_seconds))
     .bind(
         body.response_code
             .to_owned()
@@ -460,20 +604,10 @@ pub async fn edit_case_handler(
     .bind(i8_used)
     .bind(id.to_string())
     .execute(&data.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"status": "error","message": format!("{:?}", e)})),
-        )
-    })?;
+    .await?;
 
     if update_result.rows_affected() == 0 {
-        let error_response = serde_json::json!({
-            "status": "fail",
-            "message": format!("Case with ID: {} not found", id)
-        });
-        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+        return Err(AppError::NotFound(format!("Case with ID: {} not found", id)));
     }
 
     let updated_case = sqlx::query_as!(
@@ -482,13 +616,7 @@ pub async fn edit_case_handler(
         id.to_string()
     )
     .fetch_one(&data.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"status": "error","message": format!("{:?}", e)})),
-        )
-    })?;
+    .await?;
 
     let case_response = serde_json::json!({"status": "success","data": serde_json::json!({
         "case": filter_db_record(&updated_case)
@@ -497,26 +625,47 @@ pub async fn edit_case_handler(
     Ok(Json(case_response))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/cases/{id}",
+    security(("bearerAuth" = [])),
+    params(("id" = String, Path, description = "Case id")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+    )
+)]
 pub async fn delete_case_handler(
+    Extension(user): Extension<User>,
     Path(id): Path<uuid::Uuid>,
     State(data): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+) -> Result<impl IntoResponse, AppError> {
+    if !is_admin(&user) {
+        let owner: Option<String> =
+            sqlx::query_scalar!(r#"SELECT user_id FROM cases WHERE id = ?"#, id.to_string())
+                .fetch_optional(&data.db)
+                .await?;
+
+        match owner {
+            Some(owner_id) if owner_id == user.id => {}
+            Some(_) => {
+                return Err(AppError::Forbidden(
+                    "You do not have access to this case".to_string(),
+                ))
+            }
+            None => {
+                return Err(AppError::NotFound(format!("Case with ID: {} not found", id)));
+            }
+        }
+    }
+
     let query_result = sqlx::query!(r#"DELETE FROM cases WHERE id = ?"#, id.to_string())
         .execute(&data.db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"status": "error","message": format!("{:?}", e)})),
-            )
-        })?;
+        .await?;
 
     if query_result.rows_affected() == 0 {
-        let error_response = serde_json::json!({
-            "status": "fail",
-            "message": format!("Case with ID: {} not found", id)
-        });
-        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+        return Err(AppError::NotFound(format!("Case with ID: {} not found", id)));
     }
 
     Ok(StatusCode::NO_CONTENT)
@@ -551,9 +700,15 @@ fn filter_db_all_record(case: &CaseModel) -> CaseModelAllResponse {
         method: case.method.to_owned().unwrap(),
         request_body: case.request_body.to_owned().unwrap(),
         expected_result: case.expected_result.to_owned().unwrap(),
+        assertion_type: case.assertion_type.to_owned().unwrap_or_else(|| "contains".to_string()),
         category: case.category.to_owned().unwrap(),
+        headers: case.headers.to_owned().unwrap_or_else(|| "{}".to_string()),
+        timeout_ms: case.timeout_ms,
+        schedule_seconds: case.schedule_seconds,
         response_code: case.response_code.to_owned().unwrap(),
         response_body: case.response_body.to_owned().unwrap(),
+        last_status: case.last_status.to_owned().unwrap_or_default(),
+        failure_reason: case.failure_reason.to_owned().unwrap_or_default(),
         used: case.used != 0,
         createdAt: case.created_at.unwrap(),
         updatedAt: case.updated_at.unwrap(),
@@ -613,101 +768,398 @@ fn filter_db_all_record(case: &CaseModel) -> CaseModelAllResponse {
 
 
 
+/// Builds and sends the HTTP request a case describes: resolves the method
+/// via `reqwest::Method::from_str`, applies each stored header, honors
+/// `timeout_ms`, and attaches a body for methods that carry one. Called from
+/// `execute_case`, which every run path goes through, so all dispatch identically.
+async fn send_case_request(
+    client: &reqwest::Client,
+    retry_policy: &http_client::RetryPolicy,
+    case: &CaseModel,
+) -> Result<(u16, reqwest::header::HeaderMap, String, u128, String, u32), AppError> {
+    let url = format!("{}{}", case.host, case.uri);
+    let method_str = case.method.clone().unwrap_or_else(|| "GET".to_string()).to_uppercase();
+    let method = reqwest::Method::from_str(&method_str).map_err(|_| {
+        AppError::BadRequest(format!("case has an unsupported HTTP method: {}", method_str))
+    })?;
+
+    let mut builder = client.request(method.clone(), &url);
+
+    if let Some(headers_json) = case.headers.as_deref() {
+        if let Ok(headers) = serde_json::from_str::<std::collections::HashMap<String, String>>(headers_json) {
+            for (name, value) in headers {
+                builder = builder.header(name, value);
+            }
+        }
+    }
+
+    if let Some(timeout_ms) = case.timeout_ms {
+        builder = builder.timeout(std::time::Duration::from_millis(timeout_ms.max(0) as u64));
+    }
+
+    if matches!(
+        method,
+        reqwest::Method::POST | reqwest::Method::PUT | reqwest::Method::PATCH
+    ) {
+        builder = builder.body(case.request_body.clone().unwrap_or_default());
+    }
+
+    let started = std::time::Instant::now();
+    let (result, attempts) = http_client::send_with_retry(builder, retry_policy).await;
+    let response = result?;
+    let elapsed_ms = started.elapsed().as_millis();
+    let resolved_url = response.url().to_string();
+    let status = response.status().as_u16();
+    let headers = response.headers().clone();
+    let body_text = response.text().await.unwrap_or_default();
+
+    Ok((status, headers, body_text, elapsed_ms, resolved_url, attempts))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/cases/{id}/test",
+    security(("bearerAuth" = [])),
+    params(("id" = String, Path, description = "Case id")),
+    responses(
+        (status = 200, description = "Executed — see last_status/failure_reason for pass, fail, or rate-limited outcome"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+    )
+)]
 pub async fn test_case_handler(
+    Extension(user): Extension<User>,
     Path(id): Path<uuid::Uuid>,
     State(data): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let query_result = sqlx::query_as!(
+) -> Result<impl IntoResponse, AppError> {
+    let case = sqlx::query_as!(
+        CaseModel,
+        r#"SELECT * FROM cases WHERE id = ?"#,
+        id.to_string()
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::RowNotFound => AppError::NotFound(format!("Case with ID: {} not found", id)),
+        e => e.into(),
+    })?;
+
+    if !is_admin(&user) && case.user_id != user.id {
+        return Err(AppError::Forbidden(
+            "You do not have access to this case".to_string(),
+        ));
+    }
+
+    // Goes through the same request/assert/persist/case_runs path as the
+    // scheduler and batch runner, so this endpoint's runs show up in
+    // /api/cases/:id/history too instead of being invisible to it.
+    run_and_record_case(&data, case).await;
+
+    let updated_case = sqlx::query_as!(
         CaseModel,
         r#"SELECT * FROM cases WHERE id = ?"#,
         id.to_string()
     )
     .fetch_one(&data.db)
+    .await?;
+
+    let case_response = serde_json::json!({"status": "success","data": serde_json::json!({
+        "case": filter_db_all_record(&updated_case)
+    })});
+
+    Ok(Json(case_response))
+}
+
+/// Runs a single case's request + assertion pass and persists the outcome.
+/// Shared by `test_case_handler`, the batch runner, and the scheduler via
+/// `run_and_record_case`, so all three dispatch and persist identically.
+async fn execute_case(data: &Arc<AppState>, mut case: CaseModel) -> CaseRunResult {
+    let case_id = case.id.clone();
+    let title = case.title.clone();
+
+    if let Err(retry_after) = data.rate_limiter.acquire(LimitType::Host, &case.host).await {
+        let failure_reason = format!("rate limited, retry after {}s", retry_after.as_secs());
+
+        // The case row's own last_status/response_code otherwise goes stale
+        // from whatever the previous run left behind, disagreeing with the
+        // case_runs history row recorded for this attempt.
+        if let Err(e) = sqlx::query!(
+            "UPDATE cases SET response_code = NULL, response_body = NULL, last_status = ?, failure_reason = ? WHERE id = ?",
+            LastStatus::Error.as_str(),
+            failure_reason,
+            case.id
+        )
+        .execute(&data.db)
+        .await
+        {
+            let db_err: AppError = e.into();
+            eprintln!(
+                "[execute_case] failed to persist rate-limited result for case {}: {}",
+                case.id, db_err
+            );
+        }
+
+        return CaseRunResult {
+            case_id,
+            title,
+            passed: false,
+            response_code: None,
+            failure_reason: Some(failure_reason),
+        };
+    }
+
+    let (passed, response_code, failure_reason, response_body) =
+        match send_case_request(&data.http_client, &data.retry_policy, &case).await {
+            Ok((status, headers, body_text, elapsed_ms, resolved_url, attempts)) => {
+                let report = assertions::evaluate_case(
+                    case.assertion_type.as_deref(),
+                    case.expected_result.as_deref().unwrap_or(""),
+                    status,
+                    &headers,
+                    &body_text,
+                );
+                let failure_reason = if report.passed {
+                    None
+                } else {
+                    Some(format!("{} assertion(s) failed", report.failures.len()))
+                };
+                let stored_body = json!({
+                    "body": body_text,
+                    "assertions": report,
+                    "url": resolved_url,
+                    "elapsed_ms": elapsed_ms,
+                    "attempts": attempts,
+                })
+                .to_string();
+                (report.passed, Some(status.to_string()), failure_reason, stored_body)
+            }
+            Err(e) => (false, None, Some(format!("request failed: {}", e)), String::new()),
+        };
+
+    case.response_code = response_code.clone();
+    case.response_body = Some(response_body);
+    let last_status = if passed {
+        LastStatus::Passed
+    } else if response_code.is_some() {
+        LastStatus::Failed
+    } else {
+        LastStatus::Error
+    };
+
+    // A single case's persist failure shouldn't abort the rest of a batch
+    // run, but it shouldn't vanish silently either — route it through
+    // AppError so it's logged the same way every other DB error is.
+    if let Err(e) = sqlx::query!(
+        "UPDATE cases SET response_code = ?, response_body = ?, last_status = ?, failure_reason = ? WHERE id = ?",
+        case.response_code,
+        case.response_body,
+        last_status.as_str(),
+        failure_reason,
+        case.id
+    )
+    .execute(&data.db)
+    .await
+    {
+        let db_err: AppError = e.into();
+        eprintln!("[execute_case] failed to persist result for case {}: {}", case.id, db_err);
+    }
+
+    CaseRunResult {
+        case_id,
+        title,
+        passed,
+        response_code,
+        failure_reason,
+    }
+}
+
+/// Runs a case via `execute_case` and additionally records the outcome as a
+/// `case_runs` history row, so `/api/cases/:id/history` is one consistent
+/// source of truth regardless of whether the run was on-demand, batched,
+/// or scheduled.
+pub(crate) async fn run_and_record_case(data: &Arc<AppState>, case: CaseModel) -> CaseRunResult {
+    let started = std::time::Instant::now();
+    let result = execute_case(data, case).await;
+    let duration_ms = started.elapsed().as_millis() as i64;
+
+    let status = if result.passed {
+        "passed"
+    } else if result.response_code.is_some() {
+        "failed"
+    } else {
+        "error"
+    };
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let _ = sqlx::query!(
+        "INSERT INTO case_runs (id, case_id, ran_at, status, response_code, duration_ms, failure_reason) VALUES (?, ?, NOW(), ?, ?, ?, ?)",
+        run_id,
+        result.case_id,
+        status,
+        result.response_code,
+        duration_ms,
+        result.failure_reason,
+    )
+    .execute(&data.db)
     .await;
 
-    match query_result {
-        Ok(mut case) => {
-
-            // 构建请求
-            let client = reqwest::Client::new();
-            let url = format!("{}{}", case.host, case.uri);
-            let method = case.method.unwrap_or_else(|| "GET".to_string()).to_uppercase();
-    
-            // 构建请求体，如果没有提供，则默认为空
-            let body = case.request_body.clone().unwrap_or_default();
-    
-            let response = match method.as_str() {
-                "GET" => client.get(&url).send().await,
-                "POST" => client.post(&url).body(body).send().await,
-                _ => {
-                    return Err((
-                        StatusCode::METHOD_NOT_ALLOWED,
-                        Json(json!({"status": "error","message": format!("Method: {} is not supported", method)})),
-                    ))
-                }
-            };
-    
-            // 检查请求是否成功
-            match response {
-                Ok(mut res) => {
-                    case.response_code = Some(res.status().to_string());
-                    case.response_body = Some(res.text().await.unwrap_or_default());
-                    
-                    // 将更新后的模型保存回数据库
-                    sqlx::query!(
-                        "UPDATE cases SET response_code = ?, response_body = ? WHERE id = ?",
-                        case.response_code, case.response_body, id.to_string()
-                    )
-                    .execute(&data.db)
-                    .await
-                    .map_err(|e| {
-                        (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(json!({"status": "error","message": format!("{:?}", e)})),
-                        )
-                    })?;
-                    
-                    let updated_case = sqlx::query_as!(
-                        CaseModel,
-                        r#"SELECT * FROM cases WHERE id = ?"#,
-                        id.to_string()
-                    )
-                    .fetch_one(&data.db)
-                    .await
-                    .map_err(|e| {
-                        (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(json!({"status": "error","message": format!("{:?}", e)})),
-                        )
-                    })?;
-
-                    let case_response = serde_json::json!({"status": "success","data": serde_json::json!({
-                        "case": filter_db_all_record(&updated_case)
-                    })});
-    
-                    return Ok(Json(case_response));
-                }
-                Err(e) => {
-                    return Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(json!({"status": "error","message": format!("Request failed: {:?}", e)})),
-                    ))
-                }
+    result
+}
+
+/// `POST /api/cases/run` — executes many stored cases in one call, bounded
+/// by a concurrency limit so a large suite doesn't open unbounded sockets.
+pub async fn run_cases_handler(
+    Extension(user): Extension<User>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<RunCasesSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    let cases = if let Some(ids) = &body.ids {
+        let mut cases = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Ok(case) = sqlx::query_as!(
+                CaseModel,
+                r#"SELECT * FROM cases WHERE id = ? AND user_id = ?"#,
+                id,
+                user.id
+            )
+            .fetch_one(&data.db)
+            .await
+            {
+                cases.push(case);
             }
         }
-        Err(sqlx::Error::RowNotFound) => {
-            let error_response = serde_json::json!({
-                "status": "fail",
-                "message": format!("Case with ID: {} not found", id)
-            });
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
-        }
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"status": "error","message": format!("{:?}", e)})),
-            ));
+        cases
+    } else if let Some(category) = &body.category {
+        sqlx::query_as!(
+            CaseModel,
+            r#"SELECT * FROM cases WHERE user_id = ? AND category = ?"#,
+            user.id,
+            category
+        )
+        .fetch_all(&data.db)
+        .await?
+    } else if body.all_used.unwrap_or(false) {
+        sqlx::query_as!(
+            CaseModel,
+            r#"SELECT * FROM cases WHERE user_id = ? AND used = 1"#,
+            user.id
+        )
+        .fetch_all(&data.db)
+        .await?
+    } else {
+        return Err(AppError::BadRequest(
+            "specify ids, category, or all_used to select which cases to run".to_string(),
+        ));
+    };
+
+    let concurrency = std::env::var("CASE_RUN_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(8usize);
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrency));
+
+    let started = std::time::Instant::now();
+    let mut handles = Vec::with_capacity(cases.len());
+    for case in cases {
+        let data = data.clone();
+        let semaphore = semaphore.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await.unwrap();
+            run_and_record_case(&data, case).await
+        }));
+    }
+
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        if let Ok(result) = handle.await {
+            results.push(result);
         }
+    }
+
+    let passed = results.iter().filter(|r| r.passed).count();
+    let total = results.len();
+    let summary = CaseRunSummary {
+        total,
+        passed,
+        failed: total - passed,
+        duration_ms: started.elapsed().as_millis(),
+        results,
     };
-    
-} 
\ No newline at end of file
+
+    Ok(Json(json!({"status": "success", "data": summary})))
+}
+
+/// `GET /api/cases/run-suite` — runs every case owned by the authenticated
+/// user and streams results incrementally over Server-Sent Events instead
+/// of blocking until the whole suite finishes.
+pub async fn run_suite_handler(
+    Extension(user): Extension<User>,
+    State(data): State<Arc<AppState>>,
+) -> Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>> {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let cases = sqlx::query_as!(
+            CaseModel,
+            r#"SELECT * FROM cases WHERE user_id = ?"#,
+            user.id
+        )
+        .fetch_all(&data.db)
+        .await
+        .unwrap_or_default();
+
+        let concurrency = std::env::var("CASE_RUN_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(8usize);
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrency));
+
+        let mut handles = Vec::with_capacity(cases.len());
+        for case in cases {
+            let data = data.clone();
+            let semaphore = semaphore.clone();
+            let tx = tx.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await.unwrap();
+                let started = std::time::Instant::now();
+                let result = run_and_record_case(&data, case).await;
+                let status = if result.passed {
+                    "passed"
+                } else if result.response_code.is_some() {
+                    "failed"
+                } else {
+                    "error"
+                };
+                let event = json!({
+                    "case_id": result.case_id,
+                    "title": result.title,
+                    "status": status,
+                    "response_code": result.response_code,
+                    "duration_ms": started.elapsed().as_millis(),
+                });
+                if let Ok(sse_event) = Event::default().event("case").json_data(event) {
+                    let _ = tx.send(Ok(sse_event));
+                }
+                result
+            }));
+        }
+
+        let mut passed = 0usize;
+        let mut failed = 0usize;
+        let mut errored = 0usize;
+        for handle in handles {
+            match handle.await {
+                Ok(result) if result.passed => passed += 1,
+                Ok(result) if result.response_code.is_some() => failed += 1,
+                _ => errored += 1,
+            }
+        }
+
+        let summary = json!({"passed": passed, "failed": failed, "errored": errored});
+        if let Ok(sse_event) = Event::default().event("summary").json_data(summary) {
+            let _ = tx.send(Ok(sse_event));
+        }
+    });
+
+    Sse::new(UnboundedReceiverStream::new(rx)).keep_alive(KeepAlive::default())
+}
\ No newline at end of file
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::dns::{Addrs, GaiResolver, Name, Resolve, Resolving};
+
+/// Resolves a fixed set of hostnames to explicit addresses, falling back to
+/// the system resolver for everything else. Lets cases target staging hosts
+/// by name (e.g. `staging-api`) without editing `/etc/hosts`.
+#[derive(Clone)]
+pub struct StaticResolver {
+    overrides: Arc<HashMap<String, Vec<SocketAddr>>>,
+    fallback: GaiResolver,
+}
+
+impl StaticResolver {
+    /// Builds a resolver from a `host -> "ip:port"` map, as read from the
+    /// `[dns]` table of the service's TOML config.
+    pub fn from_host_map(map: HashMap<String, String>) -> Self {
+        let overrides = map
+            .into_iter()
+            .filter_map(|(host, addr)| {
+                addr.to_socket_addrs()
+                    .ok()
+                    .map(|addrs| (host, addrs.collect::<Vec<_>>()))
+            })
+            .collect();
+
+        Self {
+            overrides: Arc::new(overrides),
+            fallback: GaiResolver::new(),
+        }
+    }
+}
+
+impl Resolve for StaticResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        if let Some(addrs) = self.overrides.get(name.as_str()) {
+            let addrs = addrs.clone();
+            return Box::pin(async move { Ok(Box::new(addrs.into_iter()) as Addrs) });
+        }
+
+        let fallback = self.fallback.clone();
+        Box::pin(async move { fallback.resolve(name).await })
+    }
+}
+
+/// Builds the `reqwest::Client` shared on `AppState`: a bounded idle
+/// connection pool and a default per-request timeout, with `dns_overrides`
+/// (if non-empty) swapped in for the system resolver.
+pub fn build_client(dns_overrides: HashMap<String, String>) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder()
+        .pool_max_idle_per_host(
+            std::env::var("HTTP_CLIENT_POOL_MAX_IDLE_PER_HOST")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(32),
+        )
+        .timeout(Duration::from_secs(
+            std::env::var("HTTP_CLIENT_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+        ));
+
+    if !dns_overrides.is_empty() {
+        builder = builder.dns_resolver(Arc::new(StaticResolver::from_host_map(dns_overrides)));
+    }
+
+    builder
+        .build()
+        .expect("failed to build shared reqwest client")
+}
+
+/// Exponential backoff (with jitter) for retrying connection/timeout errors.
+/// 4xx/5xx responses are `Ok(Response)`, never retried.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        Self {
+            max_retries: std::env::var("HTTP_CLIENT_MAX_RETRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2),
+            base_delay: Duration::from_millis(
+                std::env::var("HTTP_CLIENT_RETRY_BASE_MS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(100),
+            ),
+            max_delay: Duration::from_millis(
+                std::env::var("HTTP_CLIENT_RETRY_MAX_MS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(2000),
+            ),
+        }
+    }
+
+    /// Delay before retry attempt `attempt` (1-indexed): `base_delay` doubled
+    /// per attempt, capped at `max_delay`, plus up to 50% random jitter.
+    fn delay_for(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay);
+        let jitter_ms = rand::thread_rng().gen_range(0..(backoff.as_millis() as u64 / 2).max(1));
+        backoff + Duration::from_millis(jitter_ms)
+    }
+}
+
+/// Sends `request`, retrying on connection/timeout errors per `policy`.
+/// Returns the final result alongside the number of attempts made (1 means
+/// it succeeded, or failed, on the first try).
+pub async fn send_with_retry(
+    request: reqwest::RequestBuilder,
+    policy: &RetryPolicy,
+) -> (Result<reqwest::Response, reqwest::Error>, u32) {
+    if request.try_clone().is_none() {
+        // Body can't be replayed (e.g. a stream) — send once, no retries.
+        return (request.send().await, 1);
+    }
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let this_attempt = request.try_clone().expect("checked cloneable above");
+        match this_attempt.send().await {
+            Ok(response) => return (Ok(response), attempt),
+            Err(e) if (e.is_connect() || e.is_timeout()) && attempt <= policy.max_retries => {
+                tokio::time::sleep(policy.delay_for(attempt)).await;
+            }
+            Err(e) => return (Err(e), attempt),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(2000),
+        }
+    }
+
+    #[test]
+    fn delay_for_doubles_per_attempt_up_to_max_delay() {
+        let policy = policy();
+        // base_delay <= delay < base_delay * 1.5 (jitter is up to 50%).
+        let d1 = policy.delay_for(1);
+        assert!(d1 >= Duration::from_millis(100) && d1 < Duration::from_millis(150));
+
+        let d2 = policy.delay_for(2);
+        assert!(d2 >= Duration::from_millis(200) && d2 < Duration::from_millis(300));
+
+        // Large attempts are capped at max_delay before jitter is added.
+        let d_large = policy.delay_for(20);
+        assert!(d_large >= Duration::from_millis(2000) && d_large < Duration::from_millis(3000));
+    }
+}
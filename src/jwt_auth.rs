@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::extract::cookie::CookieJar;
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use serde_json::json;
+
+use crate::{
+    model::{TokenClaims, User},
+    refresh, AppState,
+};
+
+/// Authenticates a request from the `token` cookie or an `Authorization:
+/// Bearer` header, then inserts the matching `User` as a request extension
+/// for downstream handlers to pull out with `Extension<User>`.
+pub async fn auth(
+    cookie_jar: CookieJar,
+    State(data): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let token = cookie_jar
+        .get("token")
+        .map(|cookie| cookie.value().to_string())
+        .or_else(|| {
+            req.headers()
+                .get(header::AUTHORIZATION)
+                .and_then(|auth_header| auth_header.to_str().ok())
+                .and_then(|auth_value| auth_value.strip_prefix("Bearer ").map(str::to_owned))
+        })
+        .ok_or_else(|| {
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({"status": "fail", "message": "You are not logged in, please provide a token"})),
+            )
+        })?;
+
+    let claims = decode::<TokenClaims>(
+        &token,
+        &DecodingKey::from_secret(data.env.jwt_secret.as_ref()),
+        &Validation::default(),
+    )
+    .map_err(|_| {
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"status": "fail", "message": "Invalid token"})),
+        )
+    })?
+    .claims;
+
+    // Lets a stolen or post-logout access token be rejected before its
+    // natural expiry, rather than staying valid for its full lifetime.
+    if refresh::is_jti_revoked(&data.db, &claims.jti).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"status": "error", "message": format!("Error checking token revocation: {}", e)})),
+        )
+    })? {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"status": "fail", "message": "Token has been revoked"})),
+        ));
+    }
+
+    let user = sqlx::query_as!(User, "SELECT * FROM users WHERE id = ?", claims.sub)
+        .fetch_optional(&data.db)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"status": "error", "message": format!("Error fetching user from database: {}", e)})),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({"status": "fail", "message": "The user belonging to this token no longer exists"})),
+            )
+        })?;
+
+    req.extensions_mut().insert(user);
+    Ok(next.run(req).await)
+}
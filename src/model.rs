@@ -1,5 +1,6 @@
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 #[allow(non_snake_case)]
 #[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
@@ -22,21 +23,42 @@ pub struct TokenClaims {
     pub sub: String,
     pub iat: usize,
     pub exp: usize,
+    /// Unique id for this access token, so individual tokens can be
+    /// denylisted without invalidating every token a user holds.
+    pub jti: String,
 }
 
-#[derive(Debug, Deserialize)]
+/// A hashed refresh token, issued alongside the short-lived access JWT so a
+/// client can stay logged in past the access token's expiry.
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct RefreshToken {
+    pub id: String,
+    pub user_id: String,
+    pub jti: String,
+    pub token_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub revoked: i8,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RegisterUserSchema {
     pub name: String,
     pub email: String,
     pub password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginUserSchema {
     pub email: String,
     pub password: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RefreshTokenSchema {
+    pub refresh_token: String,
+}
+
 
 pub struct CaseModel {
     pub id: String,
@@ -47,15 +69,68 @@ pub struct CaseModel {
     pub method: Option<String>,
     pub request_body:  Option<String>,
     pub expected_result:  Option<String>,
+    pub assertion_type: Option<String>,
     pub category: Option<String>,
+    /// Request headers to send, stored as a JSON object string (e.g.
+    /// `{"Authorization": "Bearer ..."}`).
+    pub headers: Option<String>,
+    pub timeout_ms: Option<i32>,
+    /// How often (in seconds) the scheduler should re-run this case
+    /// automatically. `None` means it only ever runs on demand.
+    pub schedule_seconds: Option<i32>,
     pub response_code:  Option<String>,
     pub response_body:  Option<String>,
+    pub last_status: Option<String>,
+    pub failure_reason: Option<String>,
     pub used: i8,
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// How `expected_result` should be evaluated against the live response.
+/// Stored as plain text on `CaseModel.assertion_type` so it round-trips
+/// through the same `Option<String>` shape as the rest of the row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertionType {
+    Exact,
+    Contains,
+    StatusCode,
+    JsonPath,
+    Regex,
+}
+
+impl AssertionType {
+    pub fn parse(value: Option<&str>) -> Self {
+        match value.unwrap_or("contains") {
+            "exact" => AssertionType::Exact,
+            "status_code" => AssertionType::StatusCode,
+            "json_path" => AssertionType::JsonPath,
+            "regex" => AssertionType::Regex,
+            _ => AssertionType::Contains,
+        }
+    }
+}
+
+/// Outcome of the most recent execution of a case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LastStatus {
+    Passed,
+    Failed,
+    Error,
+}
+
+impl LastStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LastStatus::Passed => "passed",
+            LastStatus::Failed => "failed",
+            LastStatus::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 #[allow(non_snake_case)]
 pub struct CaseModelResponse {
     pub id: String,
@@ -74,7 +149,48 @@ pub struct CaseModelResponse {
     pub updatedAt: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Serialize)]
+pub struct CaseRunResult {
+    pub case_id: String,
+    pub title: String,
+    pub passed: bool,
+    pub response_code: Option<String>,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaseRunSummary {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub duration_ms: u128,
+    pub results: Vec<CaseRunResult>,
+}
+
+/// A single recorded execution of a case, written by the scheduler and by
+/// `GET /api/cases/:id/test` alike, so `/api/cases/:id/history` has one
+/// consistent source of truth regardless of how the run was triggered.
+#[derive(Debug, Deserialize, Serialize, sqlx::FromRow, Clone)]
+pub struct CaseRun {
+    pub id: String,
+    pub case_id: String,
+    pub ran_at: chrono::DateTime<chrono::Utc>,
+    pub status: String,
+    pub response_code: Option<String>,
+    pub duration_ms: i64,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaseHistory {
+    pub case_id: String,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub runs: Vec<CaseRun>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 #[allow(non_snake_case)]
 pub struct CaseModelAllResponse {
     pub id: String,
@@ -85,9 +201,15 @@ pub struct CaseModelAllResponse {
     pub method: String,
     pub request_body: String,
     pub expected_result: String,
+    pub assertion_type: String,
     pub category: String,
+    pub headers: String,
+    pub timeout_ms: Option<i32>,
+    pub schedule_seconds: Option<i32>,
     pub response_code: String,
     pub response_body: String,
+    pub last_status: String,
+    pub failure_reason: String,
     pub used: bool,
     pub createdAt: chrono::DateTime<chrono::Utc>,
     pub updatedAt: chrono::DateTime<chrono::Utc>,
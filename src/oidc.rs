@@ -0,0 +1,333 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Redirect},
+    Json,
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use rand_core::{OsRng, RngCore};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::{model::TokenClaims, AppState};
+
+/// Provider URL, client id/secret and redirect URI, read from config
+/// alongside the rest of `Env` rather than hard-coded.
+pub struct OidcConfig {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+}
+
+impl OidcConfig {
+    pub fn from_env() -> Self {
+        Self {
+            issuer_url: std::env::var("OIDC_ISSUER_URL").unwrap_or_default(),
+            client_id: std::env::var("OIDC_CLIENT_ID").unwrap_or_default(),
+            client_secret: std::env::var("OIDC_CLIENT_SECRET").unwrap_or_default(),
+            redirect_uri: std::env::var("OIDC_REDIRECT_URI").unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    email: String,
+    #[serde(default)]
+    name: String,
+}
+
+fn random_url_safe(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    let digest = Sha256::digest(verifier.as_bytes());
+    base64::encode_config(digest, base64::URL_SAFE_NO_PAD)
+}
+
+async fn discover(issuer_url: &str) -> Result<ProviderMetadata, reqwest::Error> {
+    reqwest::get(format!("{}/.well-known/openid-configuration", issuer_url))
+        .await?
+        .json::<ProviderMetadata>()
+        .await
+}
+
+/// `GET /api/auth/oidc/login` — redirects the browser to the provider's
+/// authorize endpoint with a PKCE challenge. The verifier rides along in a
+/// short-lived cookie so the callback can complete the exchange without
+/// server-side session state.
+pub async fn oidc_login_handler(
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let config = OidcConfig::from_env();
+    let metadata = discover(&config.issuer_url).await.map_err(|e| {
+        (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({"status": "error", "message": format!("OIDC discovery failed: {}", e)})),
+        )
+    })?;
+
+    let verifier = random_url_safe(32);
+    let challenge = pkce_challenge(&verifier);
+    let state = random_url_safe(16);
+
+    let authorize_url = format!(
+        "{}?response_type=code&client_id={}&redirect_uri={}&scope=openid%20email%20profile&code_challenge={}&code_challenge_method=S256&state={}",
+        metadata.authorization_endpoint, config.client_id, config.redirect_uri, challenge, state
+    );
+
+    let verifier_cookie = Cookie::build("oidc_verifier", verifier)
+        .path("/")
+        .max_age(time::Duration::minutes(10))
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .finish();
+
+    let state_cookie = Cookie::build("oidc_state", state)
+        .path("/")
+        .max_age(time::Duration::minutes(10))
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .finish();
+
+    let _ = &data; // reserved for future per-tenant provider config
+    Ok((
+        [
+            (header::SET_COOKIE, verifier_cookie.to_string()),
+            (header::SET_COOKIE, state_cookie.to_string()),
+        ],
+        Redirect::to(&authorize_url),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackQuery {
+    code: String,
+    state: String,
+}
+
+/// `GET /api/auth/oidc/callback` — exchanges the code for tokens, validates
+/// the ID token against the provider's JWKS, upserts the local `User` row
+/// and issues the same `TokenClaims` JWT the rest of the app expects.
+pub async fn oidc_callback_handler(
+    State(data): State<Arc<AppState>>,
+    Query(params): Query<OidcCallbackQuery>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let expected_state = jar.get("oidc_state").map(|c| c.value().to_string());
+    if expected_state.as_deref() != Some(params.state.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"status": "fail", "message": "invalid or missing state parameter"})),
+        ));
+    }
+
+    let verifier = jar
+        .get("oidc_verifier")
+        .map(|c| c.value().to_string())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"status": "fail", "message": "missing oidc_verifier cookie"})),
+            )
+        })?;
+
+    let config = OidcConfig::from_env();
+    let metadata = discover(&config.issuer_url).await.map_err(|e| {
+        (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({"status": "error", "message": format!("OIDC discovery failed: {}", e)})),
+        )
+    })?;
+
+    let http = reqwest::Client::new();
+    let token_response: TokenResponse = http
+        .post(&metadata.token_endpoint)
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", params.code.as_str()),
+            ("redirect_uri", config.redirect_uri.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("client_secret", config.client_secret.as_str()),
+            ("code_verifier", verifier.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(json!({"status": "error", "message": format!("token exchange failed: {}", e)})),
+            )
+        })?
+        .json()
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(json!({"status": "error", "message": format!("invalid token response: {}", e)})),
+            )
+        })?;
+
+    let jwks = fetch_jwks(&http, &metadata.jwks_uri).await.map_err(|e| {
+        (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({"status": "error", "message": format!("jwks fetch failed: {}", e)})),
+        )
+    })?;
+
+    let id_claims = validate_id_token(&token_response.id_token, &jwks, &config.client_id).map_err(|e| {
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"status": "fail", "message": format!("invalid id_token: {}", e)})),
+        )
+    })?;
+
+    let user = sqlx::query_as!(
+        crate::model::User,
+        "SELECT * FROM users WHERE email = ?",
+        id_claims.email.to_ascii_lowercase()
+    )
+    .fetch_optional(&data.db)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"status": "error", "message": format!("Database error: {}", e)})),
+        )
+    })?;
+
+    let user = match user {
+        Some(user) => user,
+        None => {
+            sqlx::query!(
+                "INSERT INTO users (name, email, password, verified) VALUES (?, ?, '', 1)",
+                if id_claims.name.is_empty() { id_claims.sub.clone() } else { id_claims.name.clone() },
+                id_claims.email.to_ascii_lowercase(),
+            )
+            .execute(&data.db)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"status": "error", "message": format!("Database error: {}", e)})),
+                )
+            })?;
+
+            sqlx::query_as!(
+                crate::model::User,
+                "SELECT * FROM users WHERE email = ?",
+                id_claims.email.to_ascii_lowercase()
+            )
+            .fetch_one(&data.db)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"status": "error", "message": format!("Database error: {}", e)})),
+                )
+            })?
+        }
+    };
+
+    let now = chrono::Utc::now();
+    let claims = TokenClaims {
+        sub: user.id.to_string(),
+        iat: now.timestamp() as usize,
+        exp: (now + chrono::Duration::minutes(60)).timestamp() as usize,
+        jti: uuid::Uuid::new_v4().to_string(),
+    };
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(data.env.jwt_secret.as_ref()),
+    )
+    .unwrap();
+
+    let cookie = Cookie::build("token", token.to_owned())
+        .path("/")
+        .max_age(time::Duration::hours(1))
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .finish();
+
+    // The verifier/state cookies are single-use; clear them now that the
+    // exchange they protected has completed.
+    let clear_verifier = Cookie::build("oidc_verifier", "")
+        .path("/")
+        .max_age(time::Duration::seconds(-1))
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .finish();
+    let clear_state = Cookie::build("oidc_state", "")
+        .path("/")
+        .max_age(time::Duration::seconds(-1))
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .finish();
+
+    Ok((
+        [
+            (header::SET_COOKIE, cookie.to_string()),
+            (header::SET_COOKIE, clear_verifier.to_string()),
+            (header::SET_COOKIE, clear_state.to_string()),
+        ],
+        Json(json!({"status": "success", "token": token})),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct Jwks {
+    keys: Vec<serde_json::Value>,
+}
+
+async fn fetch_jwks(http: &reqwest::Client, jwks_uri: &str) -> Result<Jwks, reqwest::Error> {
+    http.get(jwks_uri).send().await?.json::<Jwks>().await
+}
+
+/// Validates the ID token's signature against the provider's JWKS and its
+/// `aud` claim against our own `client_id`, per the OIDC core spec. The key
+/// matching `kid` is used to verify with RS256.
+fn validate_id_token(id_token: &str, jwks: &Jwks, client_id: &str) -> Result<IdTokenClaims, String> {
+    use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+
+    let header = decode_header(id_token).map_err(|e| e.to_string())?;
+    let kid = header.kid.ok_or("id_token missing kid")?;
+
+    let jwk = jwks
+        .keys
+        .iter()
+        .find(|k| k.get("kid").and_then(|v| v.as_str()) == Some(kid.as_str()))
+        .ok_or("no matching jwk for kid")?;
+
+    let n = jwk.get("n").and_then(|v| v.as_str()).ok_or("jwk missing n")?;
+    let e = jwk.get("e").and_then(|v| v.as_str()).ok_or("jwk missing e")?;
+    let decoding_key = DecodingKey::from_rsa_components(n, e).map_err(|e| e.to_string())?;
+
+    let mut validation = Validation::new(Algorithm::RS256);
+    validation.set_audience(&[client_id]);
+
+    let data = decode::<IdTokenClaims>(id_token, &decoding_key, &validation)
+        .map_err(|e| e.to_string())?;
+    Ok(data.claims)
+}
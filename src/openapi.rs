@@ -0,0 +1,72 @@
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{
+    handler::{
+        case_list_handler, create_case_handler, delete_case_handler, edit_case_handler,
+        get_case_handler, login_user_handler, register_user_handler, test_case_handler,
+    },
+    model::{CaseModelAllResponse, CaseModelResponse, LoginUserSchema, RegisterUserSchema},
+    response::FilteredUser,
+    schema::{CreateCaseSchema, FilterOptions, UpdateCaseSchema},
+};
+
+/// Aggregates every `#[utoipa::path(...)]`-annotated handler and referenced
+/// schema into a single OpenAPI 3.0 document.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        register_user_handler,
+        login_user_handler,
+        case_list_handler,
+        create_case_handler,
+        get_case_handler,
+        edit_case_handler,
+        delete_case_handler,
+        test_case_handler,
+    ),
+    components(schemas(
+        RegisterUserSchema,
+        LoginUserSchema,
+        CreateCaseSchema,
+        UpdateCaseSchema,
+        FilterOptions,
+        FilteredUser,
+        CaseModelResponse,
+        CaseModelAllResponse,
+    )),
+    modifiers(&SecurityAddon),
+    info(
+        title = "rust-demo API",
+        description = "Case-based HTTP test runner with JWT auth.",
+        version = "1.0.0"
+    )
+)]
+pub struct ApiDoc;
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearerAuth",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .build(),
+                ),
+            );
+        }
+    }
+}
+
+/// `/api/docs` Swagger UI plus the `/api/openapi.json` document it reads,
+/// both generated from `ApiDoc` so they stay in lock-step with the handlers.
+pub fn swagger_routes() -> SwaggerUi {
+    SwaggerUi::new("/api/docs").url("/api/openapi.json", ApiDoc::openapi())
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Token-bucket limiter for a single host, refilled continuously based on
+/// elapsed wall-clock time rather than a fixed-interval timer.
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    rate: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn new(capacity: f64, rate: f64) -> Self {
+        Self {
+            tokens: capacity,
+            capacity,
+            rate,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed_secs = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed_secs * self.rate).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Returns the duration the caller must wait before a token is available,
+    /// consuming one token immediately if none was needed.
+    fn try_take(&mut self) -> Option<Duration> {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            let missing = 1.0 - self.tokens;
+            self.tokens = 0.0;
+            Some(Duration::from_secs_f64(missing / self.rate))
+        }
+    }
+}
+
+/// What a bucket is keyed on. Cases are limited per-host so that a burst
+/// against one target API can't starve requests against another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitType {
+    Host,
+}
+
+/// Shared, per-host rate limiter held on `AppState`. Mirrors the
+/// `LimitedRequester`/`LimitType` shape used for throttling elsewhere, but
+/// keyed purely by host since that's all `CaseModel` gives us to work with.
+#[derive(Debug)]
+pub struct LimitedRequester {
+    buckets: Mutex<HashMap<String, Bucket>>,
+    default_capacity: f64,
+    default_rate: f64,
+    max_wait: Duration,
+}
+
+impl LimitedRequester {
+    pub fn new(default_capacity: f64, default_rate: f64, max_wait: Duration) -> Self {
+        Self {
+            buckets: Mutex::new(HashMap::new()),
+            default_capacity,
+            default_rate,
+            max_wait,
+        }
+    }
+
+    /// Builds a limiter from env, falling back to sane defaults when the
+    /// vars are unset or unparsable.
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("RATE_LIMIT_CAPACITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5.0);
+        let rate = std::env::var("RATE_LIMIT_RATE_PER_SEC")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1.0);
+        let max_wait_secs = std::env::var("RATE_LIMIT_MAX_WAIT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10.0);
+        Self::new(capacity, rate, Duration::from_secs_f64(max_wait_secs))
+    }
+
+    /// Blocks the current task until a token is available for `host`, keyed
+    /// by `LimitType::Host`. Returns `Err(retry_after)` instead of waiting
+    /// when the required wait exceeds the configured hard cap.
+    pub async fn acquire(&self, _limit_type: LimitType, host: &str) -> Result<(), Duration> {
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap();
+                let bucket = buckets
+                    .entry(host.to_string())
+                    .or_insert_with(|| Bucket::new(self.default_capacity, self.default_rate));
+                bucket.try_take()
+            };
+
+            match wait {
+                None => return Ok(()),
+                Some(duration) if duration > self.max_wait => return Err(duration),
+                Some(duration) => tokio::time::sleep(duration).await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_take_drains_capacity_then_reports_a_wait() {
+        let mut bucket = Bucket::new(2.0, 1.0);
+        assert!(bucket.try_take().is_none());
+        assert!(bucket.try_take().is_none());
+        // Capacity exhausted: the third take needs to wait for a refill
+        // rather than going negative.
+        assert!(bucket.try_take().is_some());
+    }
+
+    #[test]
+    fn try_take_refills_over_time() {
+        let mut bucket = Bucket::new(1.0, 100.0); // 100 tokens/sec
+        assert!(bucket.try_take().is_none());
+        std::thread::sleep(Duration::from_millis(20));
+        // At 100/sec, 20ms is enough to refill well past one token.
+        assert!(bucket.try_take().is_none());
+    }
+
+    #[tokio::test]
+    async fn acquire_returns_err_when_wait_exceeds_max_wait() {
+        let limiter = LimitedRequester::new(1.0, 0.001, Duration::from_millis(1));
+        assert!(limiter.acquire(LimitType::Host, "example.com").await.is_ok());
+        // The bucket is now empty and refills far slower than max_wait allows.
+        assert!(limiter.acquire(LimitType::Host, "example.com").await.is_err());
+    }
+}
@@ -0,0 +1,34 @@
+use sha2::{Digest, Sha256};
+
+/// Refresh tokens are long-lived, so only their hash is ever persisted —
+/// the plaintext value is handed to the client once, at issue time, the
+/// same way a password never round-trips back out of the `users` table.
+pub fn hash_token(token: &str) -> String {
+    let digest = Sha256::digest(token.as_bytes());
+    hex::encode(digest)
+}
+
+pub fn generate_token() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+pub const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+/// Checks an access token's `jti` against the `refresh_tokens` table, which
+/// `jwt_auth::auth` denylists against on every request. Access tokens from
+/// login and refresh are minted together with a `refresh_tokens` row
+/// carrying the same `jti` (see `issue_refresh_token`), and that row is
+/// flipped to `revoked = 1` on logout and on refresh rotation — so this
+/// reuses that bookkeeping instead of a separate denylist table. A `jti`
+/// with no row at all (such as a token issued by the OIDC callback, which
+/// writes no refresh token) is treated as not revoked rather than rejected.
+pub async fn is_jti_revoked(pool: &sqlx::MySqlPool, jti: &str) -> Result<bool, sqlx::Error> {
+    let revoked = sqlx::query_scalar!(
+        r#"SELECT revoked FROM refresh_tokens WHERE jti = ? LIMIT 1"#,
+        jti
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(matches!(revoked, Some(flag) if flag != 0))
+}
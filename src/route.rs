@@ -7,11 +7,14 @@ use axum::{
 };
 
 use crate::{
+    oidc::{oidc_callback_handler, oidc_login_handler},
+    openapi,
     handler::{
         get_me_handler, health_checker_handler, login_user_handler, logout_handler,
-        register_user_handler,
-        create_case_handler, delete_case_handler, edit_case_handler, get_case_handler,
-        case_list_handler,test_case_handler,
+        refresh_token_handler, register_user_handler,
+        case_history_handler, create_case_handler, delete_case_handler, edit_case_handler,
+        get_case_handler, case_list_handler, run_cases_handler, run_suite_handler,
+        test_case_handler,
 
     },
     jwt_auth::auth,
@@ -23,6 +26,9 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
         .route("/api/healthchecker", get(health_checker_handler))
         .route("/api/auth/register", post(register_user_handler))
         .route("/api/auth/login", post(login_user_handler))
+        .route("/api/auth/oidc/login", get(oidc_login_handler))
+        .route("/api/auth/oidc/callback", get(oidc_callback_handler))
+        .route("/api/auth/refresh", post(refresh_token_handler))
         .route(
             "/api/auth/logout",
             get(logout_handler)
@@ -43,8 +49,29 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
             "/api/cases/:id",
             get(get_case_handler)
                 .patch(edit_case_handler)
-                .delete(delete_case_handler),
+                .delete(delete_case_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/cases/:id/test",
+            get(test_case_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/cases/:id/history",
+            get(case_history_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/cases/run",
+            post(run_cases_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/cases/run-suite",
+            get(run_suite_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
         )
-        .route("/api/cases/:id/test", get(test_case_handler))
+        .merge(openapi::swagger_routes())
         .with_state(app_state)
 }
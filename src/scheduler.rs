@@ -0,0 +1,55 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{handler::run_and_record_case, model::CaseModel, AppState};
+
+/// Polls for cases whose `schedule_seconds` interval has elapsed since their
+/// last recorded run and re-executes them, recording the outcome the same
+/// way `GET /api/cases/:id/test` does. A case with no prior runs is due
+/// immediately.
+pub fn spawn(data: Arc<AppState>) {
+    tokio::spawn(async move {
+        let poll_interval = Duration::from_secs(
+            std::env::var("SCHEDULER_POLL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+        );
+
+        loop {
+            tokio::time::sleep(poll_interval).await;
+
+            let due_cases = sqlx::query_as!(
+                CaseModel,
+                r#"SELECT c.* FROM cases c
+                   LEFT JOIN (
+                       SELECT case_id, MAX(ran_at) AS last_ran_at
+                       FROM case_runs
+                       GROUP BY case_id
+                   ) r ON r.case_id = c.id
+                   WHERE c.schedule_seconds IS NOT NULL
+                     AND (
+                         r.last_ran_at IS NULL
+                         OR r.last_ran_at <= DATE_SUB(NOW(), INTERVAL c.schedule_seconds SECOND)
+                     )"#
+            )
+            .fetch_all(&data.db)
+            .await;
+
+            let due_cases = match due_cases {
+                Ok(cases) => cases,
+                Err(e) => {
+                    eprintln!("[scheduler] failed to load due cases: {:?}", e);
+                    continue;
+                }
+            };
+
+            for case in due_cases {
+                let data = data.clone();
+                tokio::spawn(async move {
+                    run_and_record_case(&data, case).await;
+                });
+            }
+        }
+    });
+}
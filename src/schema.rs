@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Debug, Default)]
+#[derive(Deserialize, Debug, Default, ToSchema)]
 pub struct FilterOptions {
     pub page: Option<usize>,
     pub limit: Option<usize>,
@@ -11,8 +12,14 @@ pub struct ParamOptions {
     pub id: String,
 }
 
+#[derive(Deserialize, Debug, Default)]
+pub struct HistoryOptions {
+    /// Most recent N runs to return, newest first. Defaults to 20.
+    pub limit: Option<usize>,
+}
 
-#[derive(Serialize, Deserialize, Debug)]
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct CreateCaseSchema {
     pub user_id: Option<String>, // 新增字段
     pub title: String,
@@ -21,13 +28,42 @@ pub struct CreateCaseSchema {
     pub method: String,
     pub request_body: String,
     pub expected_result: String,
+    /// One of `exact`, `contains`, `status_code`, `json_path`, `regex`. Leave
+    /// unset to fall back to the legacy JSON-rule-set/substring evaluator
+    /// against `expected_result` (see `assertions::evaluate_case`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assertion_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub category: Option<String>,
+    /// Request headers to send, as a JSON object e.g. `{"Authorization": "Bearer ..."}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<i32>,
+    /// How often (in seconds) the scheduler should re-run this case
+    /// automatically. Omit to leave it on-demand only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule_seconds: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub used: Option<bool>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RunCasesSchema {
+    /// Run only cases in this category.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Run only cases with these ids.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ids: Option<Vec<String>>,
+    /// Run every case with `used = true`. Ignored if `category` or `ids` is
+    /// set; required (must be `true`) if neither is, otherwise the request
+    /// is rejected rather than silently running every used case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all_used: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct UpdateCaseSchema {
     pub user_id: Option<String>, // 新增字段
     pub title: Option<String>,
@@ -36,7 +72,11 @@ pub struct UpdateCaseSchema {
     pub method: Option<String>,
     pub request_body: Option<String>,
     pub expected_result: Option<String>,
+    pub assertion_type: Option<String>,
     pub category: Option<String>,
+    pub headers: Option<String>,
+    pub timeout_ms: Option<i32>,
+    pub schedule_seconds: Option<i32>,
     pub response_code: Option<String>,
     pub response_body: Option<String>,
     pub used: Option<bool>,